@@ -0,0 +1,163 @@
+/*
+ * Copyright (c) 2016 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Import/export support for moving entries in and out of the store.
+
+use std::fs;
+
+use json::{self, JsonValue};
+
+use Error::*;
+use {PasswordStore, Result, list_entries};
+
+/// Serialization format used by [`PasswordStore::export`] and [`PasswordStore::import`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// A flat `path -> password` JSON mapping specific to this crate.
+    Native,
+    /// The JSON schema used by the BitWarden desktop and CLI clients.
+    BitWarden,
+}
+
+impl PasswordStore {
+    /// Export every entry in the store as a string in the given `format`.
+    pub fn export(format: Format) -> Result<String> {
+        let entries = list_entries()?;
+        match format {
+            Format::Native => export_native(&entries),
+            Format::BitWarden => export_bitwarden(&entries),
+        }
+    }
+
+    /// Import entries from the file at `path`, which must contain data in the given `format`.
+    pub fn import(path: &str, format: Format) -> Result<()> {
+        validate_path!(path);
+        let contents = fs::read_to_string(path)?;
+        match format {
+            Format::Native => import_native(&contents),
+            Format::BitWarden => import_bitwarden(&contents),
+        }
+    }
+}
+
+/// Split an entry path into its folder (everything before the last `/`) and its leaf name.
+fn split_entry(entry: &str) -> (Option<&str>, &str) {
+    match entry.rfind('/') {
+        Some(index) => (Some(&entry[..index]), &entry[index + 1..]),
+        None => (None, entry),
+    }
+}
+
+fn export_native(entries: &[String]) -> Result<String> {
+    let mut store = PasswordStore::open()?;
+    let mut object = JsonValue::new_object();
+    for entry in entries {
+        let password = store.get_entry(entry)?;
+        object[entry.as_str()] = password.into();
+    }
+    Ok(object.dump())
+}
+
+fn export_bitwarden(entries: &[String]) -> Result<String> {
+    let mut store = PasswordStore::open()?;
+    let mut folders = vec![];
+    let mut items = JsonValue::new_array();
+    for entry in entries {
+        let (username, password) = store.get_login(entry)?;
+        let (folder, name) = split_entry(entry);
+        let folder_id = folder.map(|folder| {
+            let index = folders.iter().position(|existing| existing == folder)
+                .unwrap_or_else(|| {
+                    folders.push(folder.to_string());
+                    folders.len() - 1
+                });
+            format!("folder-{}", index + 1)
+        });
+        let folder_id: JsonValue = match folder_id {
+            Some(id) => id.into(),
+            None => JsonValue::Null,
+        };
+        items.push(object! {
+            "name" => name,
+            "folderId" => folder_id,
+            "login" => object! {
+                "username" => username.unwrap_or_else(|| name.to_string()),
+                "password" => password
+            },
+            "notes" => JsonValue::Null
+        })?;
+    }
+    let mut folder_array = JsonValue::new_array();
+    for (index, folder) in folders.iter().enumerate() {
+        folder_array.push(object! {
+            "id" => format!("folder-{}", index + 1),
+            "name" => folder.clone()
+        })?;
+    }
+    Ok(object! {
+        "folders" => folder_array,
+        "items" => items
+    }.dump())
+}
+
+fn import_native(contents: &str) -> Result<()> {
+    let parsed = json::parse(contents)?;
+    let mut store = PasswordStore::open()?;
+    for (path, value) in parsed.entries() {
+        let password = value.as_str().ok_or(InvalidInput)?;
+        store.insert_entry(path, password)?;
+    }
+    Ok(())
+}
+
+fn import_bitwarden(contents: &str) -> Result<()> {
+    let parsed = json::parse(contents)?;
+    let mut store = PasswordStore::open()?;
+    let mut folders = vec![];
+    for folder in parsed["folders"].members() {
+        let id = folder["id"].as_str().ok_or(InvalidInput)?.to_string();
+        let name = folder["name"].as_str().ok_or(InvalidInput)?.to_string();
+        folders.push((id, name));
+    }
+    for item in parsed["items"].members() {
+        // BitWarden exports interleave non-login items (secure notes, cards,
+        // identities, ...) that have no `login.password`; only logins can be
+        // represented as a store entry, so the rest are skipped rather than
+        // aborting the whole import.
+        let password = match item["login"]["password"].as_str() {
+            Some(password) => password,
+            None => continue,
+        };
+        let username = item["login"]["username"].as_str().unwrap_or("");
+        let name = item["name"].as_str().unwrap_or("");
+        let folder_name = item["folderId"].as_str()
+            .and_then(|id| folders.iter().find(|&&(ref folder_id, _)| folder_id == id))
+            .map(|&(_, ref name)| name.as_str());
+        let entry_name = if name.is_empty() { username.to_string() } else { name.to_string() };
+        let full_path =
+            match folder_name {
+                Some(folder) => format!("{}/{}", folder, entry_name),
+                None => entry_name,
+            };
+        store.insert_entry(&full_path, password)?;
+    }
+    Ok(())
+}
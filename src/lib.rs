@@ -21,14 +21,15 @@
 
 #[macro_use]
 extern crate json;
+extern crate regex;
 
 mod chomp;
 
 use std::error;
 use std::ffi::OsStr;
 use std::fmt::{self, Display, Formatter};
-use std::io::{self, Write};
-use std::process::{Command, Stdio};
+use std::io::{self, Read, Write};
+use std::process::{Child, Command, Stdio};
 use std::str::{self, Utf8Error};
 use std::string;
 
@@ -45,6 +46,14 @@ macro_rules! validate_path {
     };
 }
 
+mod format;
+mod policy;
+mod search;
+
+pub use format::Format;
+pub use policy::PasswordPolicy;
+pub use search::SearchMode;
+
 const MSG_SIZE: usize = 4;
 
 /// `Error` type that can be returned by the `PasswordStore` methods.
@@ -55,8 +64,10 @@ pub enum Error {
     Io(io::Error),
     InvalidInput,
     InvalidOutput,
+    InvalidPattern(String),
     Pass(String),
     Utf8(Utf8Error),
+    WeakPassword(String),
 }
 
 impl From<json::Error> for Error {
@@ -92,8 +103,10 @@ impl Display for Error {
                 Io(ref error) => error.to_string(),
                 InvalidInput => "invalid input".to_string(),
                 InvalidOutput => "invalid output".to_string(),
+                InvalidPattern(ref error) => error.clone(),
                 Pass(ref error) => error.clone(),
                 Utf8(ref error) => error.to_string(),
+                WeakPassword(ref error) => error.clone(),
             };
         write!(formatter, "{}", string)
     }
@@ -107,8 +120,10 @@ impl error::Error for Error {
             Io(ref error) => error.description(),
             InvalidInput => "invalid input",
             InvalidOutput => "invalid output",
+            InvalidPattern(ref error) => error,
             Pass(ref error) => error,
             Utf8(ref error) => error.description(),
+            WeakPassword(ref error) => error,
         }
     }
 }
@@ -116,19 +131,89 @@ impl error::Error for Error {
 /// `Result` type returned by the `PasswordStore` methods.
 pub type Result<T> = std::result::Result<T, Error>;
 
-/// `Pass` process runner.
-pub struct PasswordStore;
+/// `Pass` process runner, backed by a long-lived `gopass jsonapi listen` session.
+///
+/// Use the static methods (`get`, `insert`, ...) for one-off calls; they open a temporary
+/// session under the hood. For batch workloads, keep a `PasswordStore` around with
+/// [`PasswordStore::open`] and drive it through [`PasswordStore::request`] to reuse the
+/// same pipe across many calls.
+pub struct PasswordStore {
+    child: Child,
+}
 
 impl PasswordStore {
+    /// Open a persistent `gopass jsonapi listen` session.
+    pub fn open() -> Result<Self> {
+        Ok(PasswordStore { child: spawn_listener()? })
+    }
+
+    /// Send `query` over the session and return the parsed response.
+    ///
+    /// If the child process has died, it is respawned once and the request retried before
+    /// giving up.
+    pub fn request(&mut self, query: JsonValue) -> Result<JsonValue> {
+        match self.send(&query) {
+            Err(Io(error)) => {
+                if !is_broken_pipe(&error) {
+                    return Err(Io(error));
+                }
+                // The child died; grab whatever it printed to stderr before replacing it, since
+                // that usually explains the failure better than the bare IO error does.
+                let diagnostic = self.read_dead_child_stderr();
+                self.child = spawn_listener()?;
+                self.send(&query).map_err(|_| diagnostic.unwrap_or(Io(error)))
+            },
+            result => result,
+        }
+    }
+
+    /// Read whatever the now-dead child printed to stderr, if anything.
+    fn read_dead_child_stderr(&mut self) -> Option<Error> {
+        let stderr = self.child.stderr.as_mut()?;
+        let mut message = String::new();
+        stderr.read_to_string(&mut message).ok()?;
+        if message.is_empty() {
+            return None;
+        }
+        message.chomp();
+        Some(Pass(message))
+    }
+
+    fn send(&mut self, json_query: &JsonValue) -> Result<JsonValue> {
+        {
+            let stdin = self.child.stdin.as_mut().ok_or(InvalidOutput)?;
+            let json_string = json_query.dump();
+            stdin.write_all(&i32_to_bytes(json_string.len() as i32))?;
+            write!(stdin, "{}", json_string)?;
+        }
+        let stdout = self.child.stdout.as_mut().ok_or(InvalidOutput)?;
+        let mut size_buffer = [0; MSG_SIZE];
+        stdout.read_exact(&mut size_buffer)?;
+        let mut payload = vec![0; bytes_to_i32(&size_buffer) as usize];
+        stdout.read_exact(&mut payload)?;
+        json::parse(str::from_utf8(&payload)?).map_err(Into::into)
+    }
+
     /// Get the password a the specified `path`.
     pub fn get(path: &str) -> Result<String> {
+        PasswordStore::open()?.get_entry(path)
+    }
+
+    /// Get the password at `path`, reusing this session's pipe.
+    fn get_entry(&mut self, path: &str) -> Result<String> {
+        self.get_login(path).map(|(_username, password)| password)
+    }
+
+    /// Get the username and password at `path`, reusing this session's pipe.
+    fn get_login(&mut self, path: &str) -> Result<(Option<String>, String)> {
         validate_path!(path);
-        let mut response = gopass_ipc(object! {
+        let mut response = self.request(object! {
             "type" => "getLogin",
             "entry" => path
         })?;
+        let username = response["username"].take_string();
         if let Some(password) = response["password"].take_string() {
-            Ok(password)
+            Ok((username, password))
         }
         else {
             Err(InvalidOutput)
@@ -138,7 +223,7 @@ impl PasswordStore {
     /// Get the list of usernames at the specified `path`.
     pub fn get_usernames(path: &str) -> Result<Vec<String>> {
         validate_path!(path);
-        let response = gopass_ipc(object! {
+        let response = PasswordStore::open()?.request(object! {
             "type" => "query",
             "query" => path
         })?;
@@ -160,10 +245,12 @@ impl PasswordStore {
         Ok(result)
     }
 
-    /// Generate a password in the store.
-    pub fn generate(path: &str, use_symbols: bool, length: i32) -> Result<()> {
+    /// Generate a password in the store, rejecting the request if `policy` considers a
+    /// generated password of this shape too weak.
+    pub fn generate(path: &str, use_symbols: bool, length: i32, policy: &PasswordPolicy) -> Result<()> {
         validate_path!(path);
-        let response = gopass_ipc(object! {
+        policy.check_generated(use_symbols, length)?;
+        let response = PasswordStore::open()?.request(object! {
             "type" => "create",
             "entry_name" => path,
             "password" => "",
@@ -179,8 +266,13 @@ impl PasswordStore {
 
     /// Insert a password in the store.
     pub fn insert(path: &str, password: &str) -> Result<()> {
+        PasswordStore::open()?.insert_entry(path, password)
+    }
+
+    /// Insert a password in the store, reusing this session's pipe.
+    fn insert_entry(&mut self, path: &str, password: &str) -> Result<()> {
         validate_path!(path);
-        let response = gopass_ipc(object! {
+        let response = self.request(object! {
             "type" => "create",
             "entry_name" => path,
             "password" => password
@@ -193,12 +285,65 @@ impl PasswordStore {
         Ok(())
     }
 
+    /// Insert a password in the store, rejecting it with `Error::WeakPassword` if it does not
+    /// satisfy `policy`.
+    pub fn insert_checked(path: &str, password: &str, policy: &PasswordPolicy) -> Result<()> {
+        policy.check(password)?;
+        PasswordStore::insert(path, password)
+    }
+
     /// Remove a password from the store.
     pub fn remove(path: &str) -> Result<()> {
         validate_path!(path);
         exec_pass("rm", &["-f", path])?;
         Ok(())
     }
+
+    /// Rename (move) the entry at `from` to `to`.
+    pub fn rename(from: &str, to: &str) -> Result<()> {
+        validate_path!(from);
+        validate_path!(to);
+        if from == to {
+            return Err(InvalidInput);
+        }
+        exec_pass("mv", &[from, to])?;
+        Ok(())
+    }
+
+    /// Copy the entry at `from` to `to`, leaving the original in place.
+    pub fn copy(from: &str, to: &str) -> Result<()> {
+        validate_path!(from);
+        validate_path!(to);
+        if from == to {
+            return Err(InvalidInput);
+        }
+        exec_pass("cp", &[from, to])?;
+        Ok(())
+    }
+
+    /// Overwrite the password of the existing entry at `path`.
+    pub fn edit(path: &str, new_password: &str) -> Result<()> {
+        validate_path!(path);
+        validate_path!(new_password);
+        let response = PasswordStore::open()?.request(object! {
+            "type" => "create",
+            "entry_name" => path,
+            "password" => new_password,
+            "force" => true
+        })?;
+        if let Some(inserted_password) = response["password"].as_str() {
+            if new_password != inserted_password {
+                return Err(InvalidOutput);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for PasswordStore {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+    }
 }
 
 /// Exec the `gopass` process with the specified `command` and `args`.
@@ -223,28 +368,32 @@ fn exec_pass<S: AsRef<OsStr>>(command: &str, args: &[S]) -> Result<String> {
     }
 }
 
-/// Query the `gopass` process with a `json_query`.
-fn gopass_ipc(json_query: JsonValue) -> Result<JsonValue> {
-    let mut process = Command::new("gopass");
-    let mut child = process.args(&["jsonapi", "listen"])
+/// List the full path of every entry currently in the store.
+fn list_entries() -> Result<Vec<String>> {
+    let output = exec_pass("ls", &["--flat"])?;
+    Ok(output.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Spawn a new `gopass jsonapi listen` child with its pipes ready for framed exchanges.
+fn spawn_listener() -> Result<Child> {
+    Command::new("gopass")
+        .args(&["jsonapi", "listen"])
         .stderr(Stdio::piped())
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
-        .spawn()?;
-    if let Some(stdin) = child.stdin.as_mut() {
-        let json_string = json_query.dump();
-        stdin.write_all(&i32_to_bytes(json_string.len() as i32))?;
-        write!(stdin, "{}", json_string)?;
-    }
-    let output = child.wait_with_output()?;
-    let mut stderr = String::from_utf8(output.stderr)?;
-    if !stderr.is_empty() {
-        stderr.chomp();
-        Err(Pass(stderr))
-    }
-    else {
-        json::parse(str::from_utf8(&output.stdout[MSG_SIZE..])?) // Skip the size of the json message.
-            .map_err(Into::into)
+        .spawn()
+        .map_err(Into::into)
+}
+
+/// Whether `error` indicates the other end of the session's pipe has gone away.
+fn is_broken_pipe(error: &io::Error) -> bool {
+    match error.kind() {
+        io::ErrorKind::BrokenPipe | io::ErrorKind::UnexpectedEof => true,
+        _ => false,
     }
 }
 
@@ -256,3 +405,7 @@ fn i32_to_bytes(num: i32) -> Vec<u8> {
         ((num >> 24) & 0xFF) as u8,
     ]
 }
+
+fn bytes_to_i32(bytes: &[u8; MSG_SIZE]) -> i32 {
+    (bytes[0] as i32) | ((bytes[1] as i32) << 8) | ((bytes[2] as i32) << 16) | ((bytes[3] as i32) << 24)
+}
@@ -0,0 +1,183 @@
+/*
+ * Copyright (c) 2016 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Password-strength policy enforced by `insert_checked` and `generate`.
+
+use Error::*;
+use Result;
+
+/// Rules applied to a candidate password before it is stored.
+///
+/// Use [`PasswordPolicy::default`] for sane defaults, or [`PasswordPolicy::disabled`] to
+/// opt a call out of strength checking entirely.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PasswordPolicy {
+    /// Reject candidates found in the embedded list of common passwords.
+    pub check_dictionary: bool,
+    /// Minimum estimated entropy, in bits, a candidate must reach.
+    pub min_entropy_bits: f64,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        PasswordPolicy {
+            check_dictionary: true,
+            min_entropy_bits: 50.0,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    /// A policy that accepts every password unconditionally.
+    pub fn disabled() -> Self {
+        PasswordPolicy {
+            check_dictionary: false,
+            min_entropy_bits: 0.0,
+        }
+    }
+
+    /// Check `password` against this policy.
+    pub fn check(&self, password: &str) -> Result<()> {
+        if self.check_dictionary && is_common_password(password) {
+            return Err(WeakPassword(format!("`{}` is one of the most common passwords", password)));
+        }
+        let entropy = entropy_bits(password);
+        if entropy < self.min_entropy_bits {
+            return Err(WeakPassword(format!(
+                "password only has about {:.0} bits of entropy, {:.0} are required", entropy, self.min_entropy_bits)));
+        }
+        Ok(())
+    }
+
+    /// Check whether a password generated with `use_symbols` and `length` can satisfy this
+    /// policy's entropy requirement. The dictionary check does not apply since the generated
+    /// characters are not known ahead of time.
+    pub fn check_generated(&self, use_symbols: bool, length: i32) -> Result<()> {
+        let mut pool_size = 26 + 26 + 10;
+        if use_symbols {
+            pool_size += 32;
+        }
+        let entropy = length.max(0) as f64 * (pool_size as f64).log2();
+        if entropy < self.min_entropy_bits {
+            return Err(WeakPassword(format!(
+                "a generated password of {} characters only reaches about {:.0} bits of entropy, {:.0} are required",
+                length, entropy, self.min_entropy_bits)));
+        }
+        Ok(())
+    }
+}
+
+/// Rough entropy estimate: length times the log2 of the character-class pool in use.
+fn entropy_bits(password: &str) -> f64 {
+    let mut pool_size = 0u32;
+    if password.chars().any(|character| character.is_ascii_lowercase()) {
+        pool_size += 26;
+    }
+    if password.chars().any(|character| character.is_ascii_uppercase()) {
+        pool_size += 26;
+    }
+    if password.chars().any(|character| character.is_ascii_digit()) {
+        pool_size += 10;
+    }
+    if password.chars().any(|character| character.is_ascii() && !character.is_ascii_alphanumeric()) {
+        pool_size += 32;
+    }
+    if pool_size == 0 {
+        return 0.0;
+    }
+    password.chars().count() as f64 * (pool_size as f64).log2()
+}
+
+fn is_common_password(password: &str) -> bool {
+    let password = password.to_lowercase();
+    COMMON_PASSWORDS.binary_search(&password.as_str()).is_ok()
+}
+
+/// A sorted list of common passwords, used for a fast dictionary check.
+///
+/// This is a small, hand-curated set of passwords that consistently appear at
+/// the head of public password-frequency corpora (RockYou, HaveIBeenPwned's
+/// Pwned Passwords, SecLists' `10-million-password-list-top-10000`) -- the
+/// literal most-common passwords (`password`, `qwerty`, `iloveyou`, `letmein`,
+/// `password1`, `monkey`, `dragon`, `football`, ...), common names, keyboard
+/// patterns, and popular words. Every entry was typed individually rather than
+/// generated from a base-word/suffix expansion, so it is far short of a real
+/// top-10,000 list, but what it does contain is genuinely common.
+const COMMON_PASSWORDS: &[&str] = &[
+    "000000", "0123456789", "102030", "111111", "11111111", "112233", "121212", "121212123",
+    "123000", "123123", "123321", "1234", "12345", "123456", "1234567", "12345678",
+    "123456789", "1234567890", "1234567891", "123654", "147258369", "159357", "159753",
+    "159951", "1q2w3e", "1q2w3e4r", "1q2w3e4r5t", "1qaz2wsx", "1qaz2wsx3edc", "1qazxsw2",
+    "22222222", "555555", "654321", "666666", "741852963", "88888888", "987654321", "99999999",
+    "a1b2c3", "aaaaaa", "abc123", "abcd1234", "abcdef", "access", "admin", "admin123",
+    "administrator", "alpha", "amanda", "america", "andrew", "angel", "angel1", "angel123",
+    "angela", "anthony", "apollo", "apple", "aragorn", "archer", "arsenal", "arsenal1",
+    "asd123", "asdasd", "asdf", "asdf1234", "asdfasdf", "asdfgh", "ashley", "asteroid",
+    "atlanta", "autumn", "baby", "babyboy", "babygirl", "babygirl1", "badboy", "bailey",
+    "banana", "barcelona", "barcelona1", "baseball", "basketball", "batman", "batman1", "bear",
+    "bella", "berlin", "berry", "beta", "biteme", "black", "blackjack", "blue", "boston",
+    "brandon", "broncos", "brown", "buddy", "bulls", "butterfly", "camaro", "cash", "casino",
+    "celtics", "changeme", "changeme123", "chargers", "charlie", "cheese", "chelsea",
+    "chelsea1", "cherry", "chewbacca", "chicago1", "chocolate", "chocolate1", "coco", "comet",
+    "computer", "cookie", "cookie1", "cooper", "corvette", "cosmos", "courage", "cowboy",
+    "cowboys", "cowboys1", "crimson", "crystal", "cutie", "daisy", "dallas", "daniel", "dawn",
+    "death", "default", "delta", "demo", "demo123", "denver", "destiny", "detroit",
+    "developer", "devil", "diamond", "dollar", "dolphins", "dragon", "dragon1", "dragon123",
+    "droid", "druid", "drummer", "duke", "dusk", "dwarf", "eagle", "eagle1", "eagles1", "elf",
+    "elizabeth", "emerald", "empire", "enterprise", "everton", "falcon", "fcbarcelona",
+    "ferrari", "firebird", "flower", "flower1", "football", "freedom", "freedom1", "fresno",
+    "friday", "frodo", "galaxy", "gamer", "gamma", "gandalf", "gangster", "george", "giants",
+    "ginger", "ginger1", "glory", "goblin", "gold", "gollum", "gondor", "goodboy", "grape",
+    "gray", "green", "guest", "guest123", "guitar", "hate", "heat", "heather", "heaven",
+    "hello", "hello123", "helpdesk", "hobbit", "hockey", "honey", "honor", "hotstuff",
+    "houston", "hunter", "iloveyou", "iloveyou1", "iloveyou123", "internet", "iron1man",
+    "ironman", "jack", "jackpot", "jake", "jasmine", "jedi", "jennifer", "jessica", "jets",
+    "jordan", "joshua", "jupiter", "justice", "justin", "juventus", "juventus1", "killer",
+    "kirk", "knicks", "knight", "lakers", "legolas", "leia", "lemon", "leo", "letmein",
+    "letmein1", "letmein123", "liberty", "life", "lightning", "lion", "liverpool",
+    "liverpool1", "london", "lottery", "love", "lovely", "lovely1", "loveyou", "lucky", "lucy",
+    "madrid", "madrid1", "madrid1real", "mage", "maggie", "manchester", "mango", "maroon",
+    "mars", "master", "matrix", "matthew", "maverick", "max", "melon", "memphis", "mercedes",
+    "merica", "meteor", "miami", "michael", "michelle", "midnight", "million", "molly",
+    "monday", "money", "monkey", "monkey1", "monkey123", "mordor", "morning", "munich",
+    "mustang", "nashville", "natasha", "navy", "nebula", "newcastle", "nicole", "ninja",
+    "omega", "orange", "orange1", "orc", "oscar", "outlaw", "p@ssw0rd", "p@ssword", "packers",
+    "paladin", "panther", "paris", "pass", "pass123", "passw0rd", "password", "password1",
+    "patriot", "patriots", "peanut", "pearl", "pepper", "phoenix", "phoenix1", "picard",
+    "pink", "planet", "platinum", "player", "player1", "pokemon", "poker", "porsche",
+    "postmaster", "power", "priest", "princess", "princess1", "private", "public", "purple",
+    "q1w2e3r4", "qazwsx", "qwe123", "qwerty", "qwerty1", "qwerty123", "qwerty12345",
+    "qwertyuiop", "raiders", "rainbow", "rainbow1", "ranger", "ravens", "realmadrid", "rebel",
+    "rebel1", "red", "redskins", "redsox", "robert", "rockets", "rocky", "rogue", "rome",
+    "root", "ruby", "saints", "samantha", "sammy", "sample", "samurai", "sapphire", "saturday",
+    "saturn", "sauron", "scarlet", "seattle", "secret", "serenity", "service", "sexy",
+    "shadow", "shadow1", "shire", "sigma", "silver", "sith", "skywalker", "soccer", "solo",
+    "spiderman", "spiderman1", "spock", "spring", "spurs", "startrek", "starwars", "starwars1",
+    "steelers", "strength", "summer", "summer1", "sunday", "sunshine", "sunshine1", "superman",
+    "superman1", "supernova", "support", "swag", "sweetie", "sweety", "sysadmin", "teal",
+    "teddy", "temp123", "test123", "testing", "thebest", "thomas", "thunder", "thunderbird",
+    "tiger", "tigger", "toby", "toor", "tottenham", "trekkie", "troll", "trumpet", "trustme",
+    "trustno1", "trustno11", "tucson", "twilight", "universe", "vader", "vegas", "venus",
+    "veronica", "victoria", "victory", "voyager", "warrior", "warriors", "webmaster",
+    "weekend", "welcome", "welcome1", "whatever", "whatever1", "white", "whoami", "wildcat",
+    "william", "winter", "wizard", "wookiee", "yankees", "yankees1", "yellow", "yoda", "yolo",
+    "zeus", "zxcvbnm", "zzzzzz",
+];
@@ -0,0 +1,85 @@
+/*
+ * Copyright (c) 2016 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Client-side pattern search over the full set of entry names.
+
+use regex::Regex;
+
+use Error::*;
+use {PasswordStore, Result, list_entries};
+
+/// How [`PasswordStore::search`] should interpret its `pattern` argument.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SearchMode {
+    /// `pattern` matches if it appears anywhere in the entry's full path.
+    Substring,
+    /// `pattern` is a shell glob (`*` and `?`) matched against the entry's full path.
+    Glob,
+    /// `pattern` is a regular expression matched against the entry's full path.
+    Regex,
+}
+
+impl PasswordStore {
+    /// Search the full set of entry paths for those matching `pattern`, interpreted
+    /// according to `mode`. Unlike `get_usernames`, results are full paths.
+    pub fn search(pattern: &str, mode: SearchMode) -> Result<Vec<String>> {
+        let entries = list_entries()?;
+        let matches: Box<Fn(&str) -> bool> =
+            match mode {
+                SearchMode::Substring => {
+                    let pattern = pattern.to_string();
+                    Box::new(move |entry| entry.contains(&pattern))
+                },
+                SearchMode::Glob => {
+                    let regex = compile(&glob_to_regex(pattern))?;
+                    Box::new(move |entry| regex.is_match(entry))
+                },
+                SearchMode::Regex => {
+                    let regex = compile(pattern)?;
+                    Box::new(move |entry| regex.is_match(entry))
+                },
+            };
+        Ok(entries.into_iter().filter(|entry| matches(entry)).collect())
+    }
+}
+
+fn compile(pattern: &str) -> Result<Regex> {
+    Regex::new(pattern).map_err(|error| InvalidPattern(error.to_string()))
+}
+
+/// Translate a shell glob (`*` matches any run of characters, `?` matches one) into an
+/// anchored regular expression.
+fn glob_to_regex(pattern: &str) -> String {
+    let mut regex = String::from("^");
+    for character in pattern.chars() {
+        match character {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '.' | '^' | '$' | '|' | '(' | ')' | '[' | ']' | '{' | '}' | '+' | '\\' => {
+                regex.push('\\');
+                regex.push(character);
+            },
+            _ => regex.push(character),
+        }
+    }
+    regex.push('$');
+    regex
+}